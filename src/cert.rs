@@ -0,0 +1,212 @@
+//! Certificate context wrapper
+
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+
+use windows::core::PSTR;
+use windows::Win32::Security::Cryptography::{
+    BCryptHash, CertDuplicateCertificateContext, CertFreeCertificateChain,
+    CertFreeCertificateContext, CertGetCertificateChain, CryptQueryObject,
+    BCRYPT_SHA256_ALG_HANDLE, CERT_CHAIN_CONTEXT, CERT_CHAIN_PARA, CERT_CONTEXT, CERT_ENHKEY_USAGE,
+    CERT_QUERY_CONTENT_FLAG_CERT, CERT_QUERY_FORMAT_FLAG_ALL, CERT_QUERY_OBJECT_BLOB,
+    CERT_TRUST_ERROR_STATUS, CERT_USAGE_MATCH, CRYPTOAPI_BLOB, HCERTCHAINENGINE, HCERTSTORE,
+    USAGE_MATCH_TYPE_OR,
+};
+
+use crate::error::CngError;
+
+/// Enhanced key usage OIDs requested when building a chain (client + server auth)
+const CHAIN_USAGE_OIDS: &[&str] = &["1.3.6.1.5.5.7.3.2", "1.3.6.1.5.5.7.3.1"];
+
+/// Windows certificate context wrapper
+#[derive(Debug)]
+pub struct CertContext(*const CERT_CONTEXT);
+
+unsafe impl Send for CertContext {}
+unsafe impl Sync for CertContext {}
+
+impl CertContext {
+    /// Construct an owned wrapper from a raw context handle
+    pub fn owned(context: *const CERT_CONTEXT) -> CertContext {
+        CertContext(context)
+    }
+
+    /// Return the inner handle to the certificate context
+    pub fn inner(&self) -> *const CERT_CONTEXT {
+        self.0
+    }
+
+    /// Construct a certificate context from DER-encoded bytes
+    pub fn from_der(data: &[u8]) -> Result<CertContext, CngError> {
+        Self::from_blob(data)
+    }
+
+    /// Construct a certificate context from a PEM-encoded string
+    pub fn from_pem(data: &str) -> Result<CertContext, CngError> {
+        Self::from_blob(data.as_bytes())
+    }
+
+    fn from_blob(data: &[u8]) -> Result<CertContext, CngError> {
+        unsafe {
+            let blob = CRYPTOAPI_BLOB {
+                cbData: data.len() as u32,
+                pbData: data.as_ptr() as _,
+            };
+
+            let mut context: *mut CERT_CONTEXT = ptr::null_mut();
+            CryptQueryObject(
+                CERT_QUERY_OBJECT_BLOB,
+                &blob as *const _ as *const c_void,
+                CERT_QUERY_CONTENT_FLAG_CERT,
+                CERT_QUERY_FORMAT_FLAG_ALL,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(&mut context as *mut _ as *mut *mut c_void),
+            )?;
+
+            Ok(CertContext::owned(context))
+        }
+    }
+
+    /// Return the SHA-256 thumbprint of the certificate.
+    ///
+    /// Computed by hashing the DER-encoded certificate bytes directly, so it
+    /// does not depend on the SHA-256 hash property being cached by the OS.
+    pub fn sha256(&self) -> Result<Vec<u8>, CngError> {
+        unsafe {
+            let context = &*self.0;
+            let encoded =
+                slice::from_raw_parts(context.pbCertEncoded, context.cbCertEncoded as usize);
+
+            let mut hash = vec![0u8; 32];
+            BCryptHash(BCRYPT_SHA256_ALG_HANDLE, None, encoded, &mut hash).ok()?;
+
+            Ok(hash)
+        }
+    }
+
+    /// Build a certificate chain from this certificate up to a trust anchor.
+    ///
+    /// When `engine` is `None` the default (system) chain engine is used. The
+    /// returned [`CertChain`] carries the ordered leaf-to-root certificates and
+    /// the aggregate trust status reported by the OS.
+    pub fn build_chain(&self, engine: Option<&ChainEngine>) -> Result<CertChain, CngError> {
+        let oids = CHAIN_USAGE_OIDS
+            .iter()
+            .map(|oid| CString::new(*oid).expect("static OID without interior NUL"))
+            .collect::<Vec<_>>();
+        let mut pointers = oids
+            .iter()
+            .map(|oid| PSTR(oid.as_ptr() as _))
+            .collect::<Vec<_>>();
+
+        let mut para = CERT_CHAIN_PARA {
+            cbSize: std::mem::size_of::<CERT_CHAIN_PARA>() as u32,
+            RequestedUsage: CERT_USAGE_MATCH {
+                dwType: USAGE_MATCH_TYPE_OR,
+                Usage: CERT_ENHKEY_USAGE {
+                    cUsageIdentifier: pointers.len() as u32,
+                    rgpszUsageIdentifier: pointers.as_mut_ptr(),
+                },
+            },
+            ..Default::default()
+        };
+
+        unsafe {
+            let mut chain: *mut CERT_CHAIN_CONTEXT = ptr::null_mut();
+            CertGetCertificateChain(
+                engine.map(|e| e.0).unwrap_or_default(),
+                self.0,
+                None,
+                HCERTSTORE::default(),
+                &mut para,
+                0,
+                None,
+                &mut chain,
+            )?;
+            Ok(CertChain(chain))
+        }
+    }
+}
+
+/// Handle to a certificate chain engine
+#[derive(Debug)]
+pub struct ChainEngine(HCERTCHAINENGINE);
+
+impl ChainEngine {
+    /// Wrap a raw chain engine handle
+    pub fn owned(handle: HCERTCHAINENGINE) -> ChainEngine {
+        ChainEngine(handle)
+    }
+
+    /// Return the inner chain engine handle
+    pub fn inner(&self) -> HCERTCHAINENGINE {
+        self.0
+    }
+}
+
+/// A built certificate chain, ordered from leaf to root
+#[derive(Debug)]
+pub struct CertChain(*const CERT_CHAIN_CONTEXT);
+
+unsafe impl Send for CertChain {}
+unsafe impl Sync for CertChain {}
+
+impl CertChain {
+    /// Collect the chain certificates, ordered from leaf to root.
+    ///
+    /// Each context is duplicated so it stays valid after the chain is dropped.
+    pub fn certificates(&self) -> Vec<CertContext> {
+        unsafe {
+            let context = &*self.0;
+            if context.cChain == 0 {
+                return Vec::new();
+            }
+            let simple = &**context.rgpChain;
+            let elements = slice::from_raw_parts(simple.rgpElement, simple.cElement as usize);
+            elements
+                .iter()
+                .map(|element| {
+                    let cert = CertDuplicateCertificateContext((**element).pCertContext);
+                    CertContext::owned(cert)
+                })
+                .collect()
+        }
+    }
+
+    /// Aggregate trust error status bits reported for the chain
+    pub fn error_status(&self) -> CERT_TRUST_ERROR_STATUS {
+        unsafe { (*self.0).TrustStatus.dwErrorStatus }
+    }
+
+    /// Whether the chain was built without any trust errors
+    pub fn is_valid(&self) -> bool {
+        self.error_status().0 == 0
+    }
+}
+
+impl Drop for CertChain {
+    fn drop(&mut self) {
+        unsafe { CertFreeCertificateChain(self.0) };
+    }
+}
+
+impl Clone for CertContext {
+    fn clone(&self) -> Self {
+        CertContext(unsafe { CertDuplicateCertificateContext(self.0) })
+    }
+}
+
+impl Drop for CertContext {
+    fn drop(&mut self) {
+        unsafe {
+            CertFreeCertificateContext(self.0);
+        }
+    }
+}
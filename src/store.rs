@@ -1,22 +1,28 @@
 //! Windows certificate store wrapper
 
+use std::ffi::CString;
 use std::os::raw::c_void;
 use std::ptr;
 
 use widestring::U16CString;
 use windows::{
-    core::PCWSTR,
+    core::{PCWSTR, PSTR},
+    Win32::Foundation::E_INVALIDARG,
     Win32::Security::Cryptography::{
-        CertCloseStore, CertDuplicateCertificateContext, CertFindCertificateInStore, CertOpenStore,
-        CertStrToNameW, PFXImportCertStore, CERT_CONTEXT, CERT_FIND_ANY, CERT_FIND_FLAGS,
-        CERT_FIND_HASH, CERT_FIND_ISSUER_NAME, CERT_FIND_ISSUER_STR, CERT_FIND_SUBJECT_NAME,
+        CertAddCertificateContextToStore, CertAddStoreToCollection, CertCloseStore,
+        CertDuplicateCertificateContext, CertFindCertificateInStore, CertOpenStore, CertStrToNameW,
+        PFXExportCertStore, PFXImportCertStore, CERT_CONTEXT, CERT_ENHKEY_USAGE, CERT_FIND_ANY,
+        CERT_FIND_ENHKEY_USAGE, CERT_FIND_FLAGS, CERT_FIND_HASH, CERT_FIND_ISSUER_NAME,
+        CERT_FIND_ISSUER_STR, CERT_FIND_KEY_IDENTIFIER, CERT_FIND_SUBJECT_NAME,
         CERT_FIND_SUBJECT_STR, CERT_OPEN_STORE_FLAGS, CERT_QUERY_ENCODING_TYPE,
-        CERT_STORE_OPEN_EXISTING_FLAG, CERT_STORE_PROV_SYSTEM_W,
-        CERT_SYSTEM_STORE_CURRENT_SERVICE_ID, CERT_SYSTEM_STORE_CURRENT_USER_ID,
-        CERT_SYSTEM_STORE_LOCAL_MACHINE_ID, CERT_SYSTEM_STORE_LOCATION_SHIFT, CERT_X500_NAME_STR,
-        CRYPTOAPI_BLOB, CRYPT_EXPORTABLE, HCERTSTORE, HCRYPTPROV_LEGACY,
-        PKCS12_INCLUDE_EXTENDED_PROPERTIES, PKCS12_PREFER_CNG_KSP, PKCS_7_ASN_ENCODING,
-        X509_ASN_ENCODING,
+        CERT_STORE_ADD_ALWAYS, CERT_STORE_ADD_DISPOSITION, CERT_STORE_ADD_NEW,
+        CERT_STORE_ADD_REPLACE_EXISTING, CERT_STORE_ADD_USE_EXISTING,
+        CERT_STORE_OPEN_EXISTING_FLAG, CERT_STORE_PROV_COLLECTION, CERT_STORE_PROV_MEMORY,
+        CERT_STORE_PROV_SYSTEM_W, CERT_SYSTEM_STORE_CURRENT_SERVICE_ID,
+        CERT_SYSTEM_STORE_CURRENT_USER_ID, CERT_SYSTEM_STORE_LOCAL_MACHINE_ID,
+        CERT_SYSTEM_STORE_LOCATION_SHIFT, CERT_X500_NAME_STR, CRYPTOAPI_BLOB, CRYPT_EXPORTABLE,
+        EXPORT_PRIVATE_KEYS, HCERTSTORE, HCRYPTPROV_LEGACY, PKCS12_INCLUDE_EXTENDED_PROPERTIES,
+        PKCS12_PREFER_CNG_KSP, PKCS_7_ASN_ENCODING, X509_ASN_ENCODING,
     },
 };
 
@@ -49,6 +55,33 @@ impl CertStoreType {
     }
 }
 
+/// Disposition controlling how a certificate is added to a store.
+///
+/// Mirrors the `CERT_STORE_ADD_*` dispositions accepted by
+/// `CertAddCertificateContextToStore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertAdd {
+    /// Always add, creating a duplicate if the certificate is already present
+    Always,
+    /// Add only if the certificate is not already in the store
+    New,
+    /// Replace an existing certificate with the same issuer and serial number
+    ReplaceExisting,
+    /// Keep the existing certificate if one with the same issuer and serial number is present
+    UseExisting,
+}
+
+impl CertAdd {
+    fn as_disposition(&self) -> CERT_STORE_ADD_DISPOSITION {
+        match self {
+            CertAdd::Always => CERT_STORE_ADD_ALWAYS,
+            CertAdd::New => CERT_STORE_ADD_NEW,
+            CertAdd::ReplaceExisting => CERT_STORE_ADD_REPLACE_EXISTING,
+            CertAdd::UseExisting => CERT_STORE_ADD_USE_EXISTING,
+        }
+    }
+}
+
 /// Windows certificate store wrapper
 #[derive(Debug)]
 pub struct CertStore(HCERTSTORE);
@@ -77,6 +110,79 @@ impl CertStore {
         }
     }
 
+    /// Create a new in-memory certificate store
+    pub fn new_memory() -> Result<CertStore, CngError> {
+        unsafe {
+            let handle = CertOpenStore(
+                CERT_STORE_PROV_MEMORY,
+                CERT_QUERY_ENCODING_TYPE::default(),
+                HCRYPTPROV_LEGACY::default(),
+                CERT_OPEN_STORE_FLAGS(0),
+                ptr::null(),
+            )?;
+            Ok(CertStore(handle))
+        }
+    }
+
+    /// Add a certificate context to the store with the given disposition
+    pub fn add_cert(&self, cert: &CertContext, disposition: CertAdd) -> Result<(), CngError> {
+        unsafe {
+            CertAddCertificateContextToStore(
+                self.0,
+                cert.inner(),
+                disposition.as_disposition(),
+                None,
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Export the store as a password-protected PKCS12 blob
+    pub fn export_pkcs12(&self, password: &str) -> Result<Vec<u8>, CngError> {
+        unsafe {
+            let password = U16CString::from_str_unchecked(password);
+            // PFXExportCertStore takes a plain u32 flags value (unlike the typed
+            // CRYPT_KEY_FLAGS accepted by PFXImportCertStore in `from_pkcs12`).
+            let flags: u32 = EXPORT_PRIVATE_KEYS | PKCS12_INCLUDE_EXTENDED_PROPERTIES.0;
+
+            let mut blob = CRYPTOAPI_BLOB::default();
+            PFXExportCertStore(self.0, &mut blob, PCWSTR(password.as_ptr()), flags)?;
+
+            let mut data = vec![0u8; blob.cbData as usize];
+            blob.pbData = data.as_mut_ptr();
+            PFXExportCertStore(self.0, &mut blob, PCWSTR(password.as_ptr()), flags)?;
+
+            Ok(data)
+        }
+    }
+
+    /// Open a collection store aggregating the named system stores.
+    ///
+    /// The member stores are searched together by all `find_by_*` methods, so
+    /// issuer lookups and chain building can span e.g. both `CA` and `Root`.
+    pub fn open_collection(
+        store_type: CertStoreType,
+        names: &[&str],
+    ) -> Result<CertStore, CngError> {
+        unsafe {
+            let handle = CertOpenStore(
+                CERT_STORE_PROV_COLLECTION,
+                CERT_QUERY_ENCODING_TYPE::default(),
+                HCRYPTPROV_LEGACY::default(),
+                CERT_OPEN_STORE_FLAGS(0),
+                ptr::null(),
+            )?;
+            let collection = CertStore(handle);
+
+            for (priority, name) in names.iter().enumerate() {
+                let member = CertStore::open(store_type, name)?;
+                CertAddStoreToCollection(collection.0, member.0, 0, priority as u32)?;
+            }
+
+            Ok(collection)
+        }
+    }
+
     /// Import certificate store from PKCS12 file
     pub fn from_pkcs12(data: &[u8], password: &str) -> Result<CertStore, CngError> {
         unsafe {
@@ -139,6 +245,67 @@ impl CertStore {
         self.do_find(CERT_FIND_HASH, &hash_blob as *const _ as _)
     }
 
+    /// Find list of certificates matching the X.509 subject key identifier
+    pub fn find_by_key_identifier<D>(&self, key_id: D) -> Result<Vec<CertContext>, CngError>
+    where
+        D: AsRef<[u8]>,
+    {
+        let key_id_blob = CRYPTOAPI_BLOB {
+            cbData: key_id.as_ref().len() as u32,
+            pbData: key_id.as_ref().as_ptr() as _,
+        };
+        self.do_find(CERT_FIND_KEY_IDENTIFIER, &key_id_blob as *const _ as _)
+    }
+
+    /// Find list of certificates matching the SHA-256 thumbprint.
+    ///
+    /// Windows has no native SHA-256 find flag, so this enumerates the store and
+    /// compares each certificate's computed SHA-256 digest.
+    pub fn find_by_sha256<D>(&self, hash: D) -> Result<Vec<CertContext>, CngError>
+    where
+        D: AsRef<[u8]>,
+    {
+        let hash = hash.as_ref();
+        let mut matches = Vec::new();
+        for cert in self.find_all()? {
+            if cert.sha256()? == hash {
+                matches.push(cert);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Find list of certificates matching all of the given enhanced key usage OIDs
+    pub fn find_by_enhanced_key_usage(&self, oids: &[&str]) -> Result<Vec<CertContext>, CngError> {
+        let oids = oids
+            .iter()
+            .map(|oid| CString::new(*oid))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| CngError::Windows(windows::core::Error::from(E_INVALIDARG)))?;
+
+        let mut pointers = oids
+            .iter()
+            .map(|oid| PSTR(oid.as_ptr() as _))
+            .collect::<Vec<_>>();
+
+        let usage = CERT_ENHKEY_USAGE {
+            cUsageIdentifier: pointers.len() as u32,
+            rgpszUsageIdentifier: pointers.as_mut_ptr(),
+        };
+
+        self.do_find(CERT_FIND_ENHKEY_USAGE, &usage as *const _ as _)
+    }
+
+    /// Find list of certificates usable for TLS client authentication
+    pub fn find_client_auth(&self) -> Result<Vec<CertContext>, CngError> {
+        self.find_by_enhanced_key_usage(&["1.3.6.1.5.5.7.3.2"])
+    }
+
+    /// Find list of certificates usable for TLS server authentication
+    pub fn find_server_auth(&self) -> Result<Vec<CertContext>, CngError> {
+        self.find_by_enhanced_key_usage(&["1.3.6.1.5.5.7.3.1"])
+    }
+
     /// Get all certificates
     pub fn find_all(&self) -> Result<Vec<CertContext>, CngError> {
         self.do_find(CERT_FIND_ANY, ptr::null())